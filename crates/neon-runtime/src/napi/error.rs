@@ -21,3 +21,11 @@ pub unsafe extern "C" fn new_range_error(out: &mut Local, env: Env, code: Local,
     let status = napi::napi_create_range_error(env, code, msg, out);
     assert_eq!(status, napi::napi_status::napi_ok);
 }
+
+/// Triggers an uncaught exception from outside the scope of a JS call, e.g. from a completed
+/// async worker or a dropped resource. Node responds the same way it does to any other
+/// uncaught exception, including emitting the `uncaughtException` event.
+pub unsafe extern "C" fn fatal_exception(env: Env, error: Local) {
+    let status = napi::napi_fatal_exception(env, error);
+    assert_eq!(status, napi::napi_status::napi_ok);
+}
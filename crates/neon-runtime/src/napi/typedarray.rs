@@ -0,0 +1,60 @@
+use std::os::raw::c_void;
+
+use raw::{Env, Local};
+use TypedArrayType;
+
+use nodejs_sys as napi;
+
+/// The result of querying a typed array value for its backing storage.
+pub struct Info {
+    pub ty: TypedArrayType,
+    pub data: *mut c_void,
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// Queries a JS value known to be a typed array for its element type, data pointer, byte offset
+/// into its backing `ArrayBuffer`, and element count. The caller must already have checked that
+/// `value` is a typed array, e.g. via `tag::is_typed_array`.
+pub unsafe fn info(env: Env, value: Local) -> Info {
+    let mut ty = napi::napi_typedarray_type::int8_array;
+    let mut len: usize = 0;
+    let mut data: *mut c_void = std::ptr::null_mut();
+    let mut buffer: Local = std::mem::zeroed();
+    let mut offset: usize = 0;
+
+    let status = napi::napi_get_typed_array_info(
+        env,
+        value,
+        &mut ty,
+        &mut len,
+        &mut data,
+        &mut buffer,
+        &mut offset,
+    );
+    assert_eq!(status, napi::napi_status::napi_ok);
+
+    Info {
+        ty: convert_type(ty),
+        data,
+        offset,
+        len,
+    }
+}
+
+fn convert_type(ty: napi::napi_typedarray_type) -> TypedArrayType {
+    use napi::napi_typedarray_type::*;
+    match ty {
+        int8_array => TypedArrayType::I8,
+        uint8_array => TypedArrayType::U8,
+        uint8_clamped_array => TypedArrayType::U8Clamped,
+        int16_array => TypedArrayType::I16,
+        uint16_array => TypedArrayType::U16,
+        int32_array => TypedArrayType::I32,
+        uint32_array => TypedArrayType::U32,
+        float32_array => TypedArrayType::F32,
+        float64_array => TypedArrayType::F64,
+        bigint64_array => TypedArrayType::I64,
+        biguint64_array => TypedArrayType::U64,
+    }
+}
@@ -0,0 +1,153 @@
+//! Internal machinery backing the `Borrow`/`BorrowMut` loan ledger.
+
+use std::collections::HashMap;
+use std::os::raw::c_void;
+
+use borrow::LoanError;
+
+/// A type that can hand back a raw pointer to its contents, used as the loan ledger's key.
+pub trait Pointer {
+    unsafe fn as_ptr(&self) -> *mut c_void;
+    unsafe fn as_mut(&mut self) -> *mut c_void;
+}
+
+/// The state of an outstanding loan for a single address.
+#[derive(Clone, Copy)]
+enum LoanState {
+    /// `n` simultaneous immutable loans. Settles back to unborrowed once `n` reaches zero.
+    Shared(usize),
+
+    /// A single exclusive (mutable) loan.
+    Exclusive,
+}
+
+/// Tracks outstanding loans by address, enforcing Rust's aliasing rules (many readers XOR one
+/// writer) across JS value contents that may be reachable from more than one handle (e.g. two
+/// `TypedArray` views over the same backing `ArrayBuffer`).
+#[derive(Default)]
+pub struct Ledger {
+    loans: HashMap<*const c_void, LoanState>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger {
+            loans: HashMap::new(),
+        }
+    }
+
+    /// Registers an immutable loan for `p`. Reentrant: any number of immutable loans for the
+    /// same address may be outstanding at once.
+    pub fn try_borrow(&mut self, p: *const c_void) -> Result<(), LoanError> {
+        match self.loans.get_mut(&p) {
+            None => {
+                self.loans.insert(p, LoanState::Shared(1));
+                Ok(())
+            }
+            Some(LoanState::Shared(count)) => {
+                *count += 1;
+                Ok(())
+            }
+            Some(LoanState::Exclusive) => Err(LoanError::Mutating(p)),
+        }
+    }
+
+    /// Registers an exclusive loan for `p`. Fails if any loan, shared or exclusive, is already
+    /// outstanding for this address.
+    pub fn try_borrow_mut(&mut self, p: *const c_void) -> Result<(), LoanError> {
+        match self.loans.get(&p) {
+            None => {
+                self.loans.insert(p, LoanState::Exclusive);
+                Ok(())
+            }
+            Some(LoanState::Shared(_)) => Err(LoanError::Frozen(p)),
+            Some(LoanState::Exclusive) => Err(LoanError::Mutating(p)),
+        }
+    }
+
+    /// Releases one immutable loan for `p`, settling the address once the shared count reaches
+    /// zero.
+    pub fn settle(&mut self, p: *const c_void) {
+        let settled = match self.loans.get_mut(&p) {
+            Some(LoanState::Shared(count)) => {
+                *count -= 1;
+                *count == 0
+            }
+            _ => true,
+        };
+        if settled {
+            self.loans.remove(&p);
+        }
+    }
+
+    /// Releases the exclusive loan for `p`, settling the address.
+    pub fn settle_mut(&mut self, p: *const c_void) {
+        self.loans.remove(&p);
+    }
+
+    /// Atomically transitions a single outstanding immutable loan for `p` to an exclusive one.
+    ///
+    /// Succeeds only when the immutable loan being upgraded is the *sole* outstanding loan for
+    /// the address; otherwise fails and leaves the ledger untouched.
+    pub fn try_upgrade(&mut self, p: *const c_void) -> Result<(), LoanError> {
+        match self.loans.get(&p) {
+            Some(LoanState::Shared(1)) => {
+                self.loans.insert(p, LoanState::Exclusive);
+                Ok(())
+            }
+            Some(LoanState::Shared(_)) => Err(LoanError::Frozen(p)),
+            Some(LoanState::Exclusive) | None => Err(LoanError::Mutating(p)),
+        }
+    }
+
+    /// Transitions the outstanding exclusive loan for `p` to a single immutable one. Always
+    /// succeeds: an exclusive loan has no other outstanding loans to conflict with.
+    pub fn downgrade(&mut self, p: *const c_void) {
+        self.loans.insert(p, LoanState::Shared(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_loans_are_reentrant_and_settle_by_count() {
+        let mut ledger = Ledger::new();
+        let p = 0x1 as *const c_void;
+
+        ledger.try_borrow(p).unwrap();
+        ledger.try_borrow(p).unwrap();
+        assert!(ledger.try_borrow_mut(p).is_err());
+
+        ledger.settle(p);
+        assert!(ledger.try_borrow_mut(p).is_err());
+
+        ledger.settle(p);
+        assert!(ledger.try_borrow_mut(p).is_ok());
+    }
+
+    #[test]
+    fn upgrade_succeeds_only_for_the_sole_shared_loan() {
+        let mut ledger = Ledger::new();
+        let p = 0x2 as *const c_void;
+
+        ledger.try_borrow(p).unwrap();
+        assert!(ledger.try_upgrade(p).is_ok());
+        ledger.settle_mut(p);
+
+        ledger.try_borrow(p).unwrap();
+        ledger.try_borrow(p).unwrap();
+        assert!(ledger.try_upgrade(p).is_err());
+    }
+
+    #[test]
+    fn downgrade_allows_a_further_shared_loan() {
+        let mut ledger = Ledger::new();
+        let p = 0x3 as *const c_void;
+
+        ledger.try_borrow_mut(p).unwrap();
+        ledger.downgrade(p);
+        assert!(ledger.try_borrow(p).is_ok());
+    }
+}
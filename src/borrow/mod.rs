@@ -4,9 +4,12 @@ pub(crate) mod internal;
 
 use std::ops::{Deref, DerefMut, Drop};
 use std::fmt;
+use std::mem;
 use std::os::raw::c_void;
+use std::slice;
 
 use context::Lock;
+use neon_runtime::TypedArrayType;
 use self::internal::Pointer;
 
 /// A trait for JS values whose internal contents can be borrowed immutably by Rust while the JS engine is locked.
@@ -58,8 +61,16 @@ pub enum LoanError {
     /// Indicates that there is already an outstanding mutable loan for the object at this address.
     Mutating(*const c_void),
 
-    /// Indicates that there is already an outstanding immutable loan for the object at this address.
-    Frozen(*const c_void)
+    /// Indicates that a mutable loan (a fresh `try_borrow_mut` or a `try_upgrade`) conflicts with
+    /// one or more outstanding immutable loans for the object at this address. Immutable loans
+    /// are reentrant, so this is never returned for a second immutable loan.
+    Frozen(*const c_void),
+
+    /// Indicates that a typed array view was borrowed as the wrong element type.
+    WrongType {
+        expected: TypedArrayType,
+        actual: TypedArrayType,
+    },
 
 }
 
@@ -72,6 +83,9 @@ impl fmt::Display for LoanError {
             LoanError::Frozen(p) => {
                 write!(f, "object at {:?} is frozen", p)
             }
+            LoanError::WrongType { expected, actual } => {
+                write!(f, "expected a typed array of type {:?}, found {:?}", expected, actual)
+            }
         }
     }
 }
@@ -97,6 +111,25 @@ impl<'a, T: Pointer> Drop for Ref<'a, T> {
     }
 }
 
+impl<'a, T: Pointer> Ref<'a, T> {
+    /// Attempts to upgrade this immutable loan to a mutable one.
+    ///
+    /// Succeeds only if this is the sole outstanding loan for the object's contents; otherwise
+    /// fails with a `LoanError`. This `Ref` is consumed either way, so on failure its immutable
+    /// loan is released.
+    pub fn try_upgrade(self, lock: &'a Lock<'a>) -> Result<RefMut<'a, T>, LoanError> {
+        {
+            let mut ledger = lock.ledger.borrow_mut();
+            ledger.try_upgrade(unsafe { self.pointer.as_ptr() })?;
+        }
+        // The ledger entry now reflects a mutable loan, so the loan transfers to the new
+        // `RefMut` without running `Ref`'s `Drop` (which would settle it back to unborrowed).
+        let pointer = unsafe { std::ptr::read(&self.pointer) };
+        mem::forget(self);
+        Ok(RefMut { pointer, lock })
+    }
+}
+
 impl<'a, T: Pointer> Deref for Ref<'a, T> {
     type Target = T;
 
@@ -126,6 +159,25 @@ impl<'a, T: Pointer> Drop for RefMut<'a, T> {
     }
 }
 
+impl<'a, T: Pointer> RefMut<'a, T> {
+    /// Downgrades this mutable loan to an immutable one.
+    ///
+    /// Unlike `try_upgrade`, this always succeeds: a mutable loan is already exclusive, so there
+    /// is nothing else to conflict with.
+    pub fn downgrade(mut self) -> Ref<'a, T> {
+        {
+            let mut ledger = self.lock.ledger.borrow_mut();
+            ledger.downgrade(unsafe { self.pointer.as_mut() });
+        }
+        let lock = self.lock;
+        // The ledger entry now reflects an immutable loan, so the loan transfers to the new
+        // `Ref` without running `RefMut`'s `Drop` (which would settle it back to unborrowed).
+        let pointer = unsafe { std::ptr::read(&self.pointer) };
+        mem::forget(self);
+        Ref { pointer, lock }
+    }
+}
+
 impl<'a, T: Pointer> Deref for RefMut<'a, T> {
     type Target = T;
 
@@ -139,3 +191,162 @@ impl<'a, T: Pointer> DerefMut for RefMut<'a, T> {
         &mut self.pointer
     }
 }
+
+/// A Rust type that corresponds to the element type of a JS typed array.
+pub trait Binary: Copy {
+    /// The JS typed array element type that this Rust type corresponds to.
+    const ARRAY_TYPE: TypedArrayType;
+}
+
+impl Binary for u8 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::U8;
+}
+
+impl Binary for i8 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::I8;
+}
+
+impl Binary for u16 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::U16;
+}
+
+impl Binary for i16 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::I16;
+}
+
+impl Binary for u32 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::U32;
+}
+
+impl Binary for i32 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::I32;
+}
+
+impl Binary for u64 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::U64;
+}
+
+impl Binary for i64 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::I64;
+}
+
+impl Binary for f32 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::F32;
+}
+
+impl Binary for f64 {
+    const ARRAY_TYPE: TypedArrayType = TypedArrayType::F64;
+}
+
+/// The internal representation of a borrowed JS typed array (`Int32Array`, `Float64Array`, etc.).
+///
+/// Unlike a plain `Pointer`, this tracks the address of the backing `ArrayBuffer` separately
+/// from the view's own data pointer, so that the loan ledger can key on the buffer: two typed
+/// array views over the same `ArrayBuffer` correctly conflict, even though each view's data
+/// pointer is offset differently into the buffer.
+pub struct TypedArrayTarget {
+    buffer: *mut c_void,
+    data: *mut c_void,
+    offset: usize,
+    ty: TypedArrayType,
+    len: usize,
+}
+
+impl TypedArrayTarget {
+    /// Constructs a target for a typed array view.
+    ///
+    /// `buffer` is the address of the backing `ArrayBuffer`, `offset` is the byte offset of this
+    /// view into that buffer, `ty` is the view's element type, and `len` is its element count.
+    pub(crate) unsafe fn new(
+        buffer: *mut c_void,
+        offset: usize,
+        ty: TypedArrayType,
+        len: usize,
+    ) -> Self {
+        let data = (buffer as *mut u8).add(offset) as *mut c_void;
+        TypedArrayTarget { buffer, data, offset, ty, len }
+    }
+
+    fn check_type<T: Binary>(&self) -> Result<(), LoanError> {
+        if self.ty == T::ARRAY_TYPE {
+            Ok(())
+        } else {
+            Err(LoanError::WrongType { expected: T::ARRAY_TYPE, actual: self.ty })
+        }
+    }
+}
+
+impl Pointer for TypedArrayTarget {
+    unsafe fn as_ptr(&self) -> *mut c_void {
+        self.buffer
+    }
+
+    unsafe fn as_mut(&mut self) -> *mut c_void {
+        self.buffer
+    }
+}
+
+impl<'a> Ref<'a, TypedArrayTarget> {
+    /// Reinterprets the borrowed typed array as a slice of `T`.
+    ///
+    /// Panics if `T` doesn't match the typed array's actual element type.
+    pub fn as_slice<T: Binary>(&self) -> &[T] {
+        match self.try_as_slice() {
+            Ok(slice) => slice,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Reinterprets the borrowed typed array as a slice of `T`.
+    ///
+    /// Fails with a `LoanError` if `T` doesn't match the typed array's actual element type.
+    pub fn try_as_slice<T: Binary>(&self) -> Result<&[T], LoanError> {
+        self.pointer.check_type::<T>()?;
+        debug_assert_eq!(self.pointer.offset % mem::size_of::<T>(), 0);
+        Ok(unsafe { slice::from_raw_parts(self.pointer.data as *const T, self.pointer.len) })
+    }
+}
+
+impl<'a> RefMut<'a, TypedArrayTarget> {
+    /// Reinterprets the borrowed typed array as a mutable slice of `T`.
+    ///
+    /// Panics if `T` doesn't match the typed array's actual element type.
+    pub fn as_mut_slice<T: Binary>(&mut self) -> &mut [T] {
+        match self.try_as_mut_slice() {
+            Ok(slice) => slice,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Reinterprets the borrowed typed array as a mutable slice of `T`.
+    ///
+    /// Fails with a `LoanError` if `T` doesn't match the typed array's actual element type.
+    pub fn try_as_mut_slice<T: Binary>(&mut self) -> Result<&mut [T], LoanError> {
+        self.pointer.check_type::<T>()?;
+        debug_assert_eq!(self.pointer.offset % mem::size_of::<T>(), 0);
+        Ok(unsafe { slice::from_raw_parts_mut(self.pointer.data as *mut T, self.pointer.len) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_type_rejects_mismatched_element_type() {
+        let target = unsafe { TypedArrayTarget::new(0x1 as *mut c_void, 0, TypedArrayType::I32, 4) };
+        match target.check_type::<f64>() {
+            Err(LoanError::WrongType { expected, actual }) => {
+                assert_eq!(expected, TypedArrayType::F64);
+                assert_eq!(actual, TypedArrayType::I32);
+            }
+            _ => panic!("expected LoanError::WrongType"),
+        }
+    }
+
+    #[test]
+    fn check_type_accepts_matching_element_type() {
+        let target = unsafe { TypedArrayTarget::new(0x1 as *mut c_void, 0, TypedArrayType::F64, 4) };
+        assert!(target.check_type::<f64>().is_ok());
+    }
+}
@@ -1,6 +1,10 @@
 //! Types and traits representing JavaScript error values.
 
+use std::any::Any;
+use std::cell::RefCell;
 use std::panic::{catch_unwind, UnwindSafe};
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Once;
 
 use neon_runtime;
 use neon_runtime::raw;
@@ -69,6 +73,19 @@ impl JsError {
         })
     }
 
+    /// Creates a direct instance of the [`Error`](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/Error) class, setting its `code` property to `code`.
+    ///
+    /// This is useful for surfacing a stable, machine-readable error code (following Node's
+    /// `ERR_*` conventions) alongside a human-readable message, so JS callers can match on
+    /// `err.code` instead of parsing `err.message`.
+    pub fn error_with_code<'a, C: Context<'a>, S: AsRef<str>, K: AsRef<str>>(
+        cx: &mut C,
+        code: K,
+        msg: S,
+    ) -> NeonResult<Handle<'a, JsError>> {
+        Self::build_with_code(cx, code, msg, neon_runtime::error::new_error)
+    }
+
     /// Creates an instance of the [`TypeError`](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/TypeError) class.
     pub fn type_error<'a, C: Context<'a>, S: AsRef<str>>(
         cx: &mut C,
@@ -101,6 +118,15 @@ impl JsError {
         })
     }
 
+    /// Creates an instance of the [`TypeError`](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/TypeError) class, setting its `code` property to `code`.
+    pub fn type_error_with_code<'a, C: Context<'a>, S: AsRef<str>, K: AsRef<str>>(
+        cx: &mut C,
+        code: K,
+        msg: S,
+    ) -> NeonResult<Handle<'a, JsError>> {
+        Self::build_with_code(cx, code, msg, neon_runtime::error::new_type_error)
+    }
+
     /// Creates an instance of the [`RangeError`](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/RangeError) class.
     pub fn range_error<'a, C: Context<'a>, S: AsRef<str>>(
         cx: &mut C,
@@ -132,6 +158,180 @@ impl JsError {
             true
         })
     }
+
+    /// Creates an instance of the [`RangeError`](https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/RangeError) class, setting its `code` property to `code`.
+    pub fn range_error_with_code<'a, C: Context<'a>, S: AsRef<str>, K: AsRef<str>>(
+        cx: &mut C,
+        code: K,
+        msg: S,
+    ) -> NeonResult<Handle<'a, JsError>> {
+        Self::build_with_code(cx, code, msg, neon_runtime::error::new_range_error)
+    }
+
+    /// Shared body for `error_with_code`/`type_error_with_code`/`range_error_with_code`: builds
+    /// the JS string for `code`, invokes `new` to construct the error with it, and (on
+    /// `legacy-runtime`, where the constructor has no `code` parameter) sets the `code` property
+    /// afterwards.
+    fn build_with_code<'a, C: Context<'a>, S: AsRef<str>, K: AsRef<str>>(
+        cx: &mut C,
+        code: K,
+        msg: S,
+        #[cfg(feature = "napi-runtime")] new: unsafe extern "C" fn(
+            &mut raw::Local,
+            raw::Env,
+            raw::Local,
+            raw::Local,
+        ),
+        #[cfg(feature = "legacy-runtime")] new: unsafe extern "C" fn(&mut raw::Local, raw::Local),
+    ) -> NeonResult<Handle<'a, JsError>> {
+        #[cfg(feature = "legacy-runtime")]
+        let msg = cx.string(msg.as_ref());
+
+        #[cfg(feature = "napi-runtime")]
+        let (ptr, len) = if let Some(small) = Utf8::from(msg.as_ref()).into_small() {
+            small.lower()
+        } else {
+            return Err(Throw);
+        };
+        #[cfg(feature = "napi-runtime")]
+        let (code_ptr, code_len) = if let Some(small) = Utf8::from(code.as_ref()).into_small() {
+            small.lower()
+        } else {
+            return Err(Throw);
+        };
+        let err = build(|out| unsafe {
+            #[cfg(feature = "napi-runtime")]
+            {
+                let mut local: raw::Local = std::mem::zeroed();
+                let mut code_local: raw::Local = std::mem::zeroed();
+                neon_runtime::string::new(&mut local, cx.env().to_raw(), ptr, len);
+                neon_runtime::string::new(&mut code_local, cx.env().to_raw(), code_ptr, code_len);
+                new(out, cx.env().to_raw(), code_local, local);
+            }
+            #[cfg(feature = "legacy-runtime")]
+            new(out, msg.to_raw());
+            true
+        })?;
+        #[cfg(feature = "legacy-runtime")]
+        {
+            let code = cx.string(code.as_ref());
+            err.set(cx, "code", code)?;
+        }
+        Ok(err)
+    }
+
+    /// Raises an uncaught exception outside of a JS call frame, e.g. from a completed async
+    /// worker, a dropped resource, or a background callback. Unlike a normal throw, there is no
+    /// JS frame to catch this: Node treats it the same as any other uncaught exception,
+    /// including emitting the `uncaughtException` event.
+    #[cfg(feature = "napi-runtime")]
+    pub fn fatal<'a, C: Context<'a>>(cx: &mut C, err: Handle<'a, JsError>) {
+        unsafe {
+            neon_runtime::error::fatal_exception(cx.env().to_raw(), err.to_raw());
+        }
+    }
+}
+
+/// The source location a Rust panic originated from.
+#[derive(Clone, Debug)]
+pub struct PanicLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Structured information about a captured Rust panic, passed to a handler registered with
+/// [`set_panic_handler`].
+pub struct PanicInfo {
+    payload: Box<dyn Any + Send>,
+
+    /// The location the panic was raised from, if it could be captured.
+    pub location: Option<PanicLocation>,
+
+    /// A captured backtrace, rendered as text, if backtraces are enabled
+    /// (see [`std::backtrace::Backtrace`]).
+    pub backtrace: Option<String>,
+}
+
+impl PanicInfo {
+    /// Returns the panic payload as a string, for the common case of a panic raised via
+    /// `panic!("...")` or `.expect("...")`.
+    pub fn payload_str(&self) -> Option<&str> {
+        if let Some(s) = self.payload.downcast_ref::<String>() {
+            Some(s.as_str())
+        } else if let Some(s) = self.payload.downcast_ref::<&str>() {
+            Some(*s)
+        } else {
+            None
+        }
+    }
+
+    /// Attempts to downcast the panic payload to a concrete type, for modules that panic with a
+    /// domain-specific payload rather than a string.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.payload.downcast_ref::<T>()
+    }
+}
+
+/// A handler that maps a captured Rust panic to an `(error.code, error.message)` pair, for
+/// modules that want to surface domain-specific errors instead of the default
+/// `"ERR_NEON_PANIC"` code and the panic payload as message.
+type PanicHandler = dyn Fn(&PanicInfo) -> (String, String) + Send + Sync;
+
+static PANIC_HANDLER: AtomicPtr<Box<PanicHandler>> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Registers a handler that maps Rust panics caught at the JS boundary to a `(code, message)`
+/// pair, instead of the default `"ERR_NEON_PANIC"` code and formatted message. Replaces any
+/// previously registered handler.
+///
+/// The previously registered handler, if any, is intentionally leaked rather than freed: a
+/// concurrent panic elsewhere may still be dereferencing it, and handlers are expected to be
+/// registered once at module startup rather than churned at runtime.
+pub fn set_panic_handler<F>(handler: F)
+where
+    F: Fn(&PanicInfo) -> (String, String) + Send + Sync + 'static,
+{
+    let boxed: Box<PanicHandler> = Box::new(handler);
+    let ptr = Box::into_raw(Box::new(boxed));
+    PANIC_HANDLER.swap(ptr, Ordering::SeqCst);
+}
+
+thread_local! {
+    static LAST_PANIC_LOCATION: RefCell<Option<PanicLocation>> = RefCell::new(None);
+}
+
+fn ensure_panic_hook_installed() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if let Some(location) = info.location() {
+                LAST_PANIC_LOCATION.with(|cell| {
+                    *cell.borrow_mut() = Some(PanicLocation {
+                        file: location.file().to_string(),
+                        line: location.line(),
+                        column: location.column(),
+                    });
+                });
+            }
+            default_hook(info);
+        }));
+    });
+}
+
+#[cfg(feature = "napi-runtime")]
+unsafe fn set_string_property(env: raw::Env, target: raw::Local, key: &str, value: &str) {
+    let (data, len) = Utf8::from(value).truncate().lower();
+    let mut local: raw::Local = std::mem::zeroed();
+    neon_runtime::string::new(&mut local, env, data, len);
+    neon_runtime::object::set_property(env, target, key, local);
+}
+
+#[cfg(feature = "napi-runtime")]
+unsafe fn set_number_property(env: raw::Env, target: raw::Local, key: &str, value: f64) {
+    let mut local: raw::Local = std::mem::zeroed();
+    neon_runtime::primitive::number(&mut local, env, value);
+    neon_runtime::object::set_property(env, target, key, local);
 }
 
 pub(crate) fn convert_panics<
@@ -143,21 +343,43 @@ pub(crate) fn convert_panics<
     cx: C,
     f: F,
 ) -> NeonResult<T> {
+    ensure_panic_hook_installed();
+
     #[cfg(feature = "napi-runtime")]
     let env = cx.env().to_raw();
     match catch_unwind(move || f(cx)) {
         Ok(result) => result,
-        Err(panic) => {
-            let msg = if let Some(string) = panic.downcast_ref::<String>() {
-                format!("internal error in Neon module: {}", string)
-            } else if let Some(str) = panic.downcast_ref::<&str>() {
-                format!("internal error in Neon module: {}", str)
+        Err(payload) => {
+            let location = LAST_PANIC_LOCATION.with(|cell| cell.borrow_mut().take());
+            let backtrace = std::backtrace::Backtrace::capture();
+            let backtrace = if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+                Some(backtrace.to_string())
+            } else {
+                None
+            };
+            let info = PanicInfo {
+                payload,
+                location,
+                backtrace,
+            };
+
+            let handler_ptr = PANIC_HANDLER.load(Ordering::SeqCst);
+            let (code, msg) = if handler_ptr.is_null() {
+                let payload_msg = info
+                    .payload_str()
+                    .unwrap_or("internal error in Neon module");
+                (
+                    "ERR_NEON_PANIC".to_string(),
+                    payload_msg.to_string(),
+                )
             } else {
-                "internal error in Neon module".to_string()
+                let handler: &PanicHandler = unsafe { &**handler_ptr };
+                handler(&info)
             };
-            println!("{}", msg);
+
             #[cfg(feature = "legacy-runtime")]
             {
+                let msg = format!("{} [{}]", msg, code);
                 let (data, len) = Utf8::from(&msg[..]).truncate().lower();
                 unsafe {
                     neon_runtime::error::throw_error_from_utf8(data, len);
@@ -166,12 +388,26 @@ pub(crate) fn convert_panics<
             }
             #[cfg(feature = "napi-runtime")]
             {
-                let (data, len) = Utf8::from(&msg[..]).truncate().lower();
+                let (msg_data, msg_len) = Utf8::from(&msg[..]).truncate().lower();
+                let (code_data, code_len) = Utf8::from(&code[..]).truncate().lower();
                 unsafe {
-                    let mut local: raw::Local = std::mem::zeroed();
+                    let mut msg_local: raw::Local = std::mem::zeroed();
+                    let mut code_local: raw::Local = std::mem::zeroed();
                     let mut error: raw::Local = std::mem::zeroed();
-                    neon_runtime::string::new(&mut local, env, data, len);
-                    neon_runtime::error::new_error(&mut error, env, std::ptr::null_mut(), local);
+                    neon_runtime::string::new(&mut msg_local, env, msg_data, msg_len);
+                    neon_runtime::string::new(&mut code_local, env, code_data, code_len);
+                    neon_runtime::error::new_error(&mut error, env, code_local, msg_local);
+
+                    if let Some(loc) = &info.location {
+                        set_string_property(env, error, "fileName", &loc.file);
+                        set_number_property(env, error, "lineNumber", loc.line as f64);
+                        set_number_property(env, error, "columnNumber", loc.column as f64);
+                    }
+
+                    if let Some(stack) = &info.backtrace {
+                        set_string_property(env, error, "stack", stack);
+                    }
+
                     neon_runtime::error::throw(env, error);
                 };
                 Err(Throw)
@@ -0,0 +1,78 @@
+//! Types and traits representing JavaScript typed array values (`Int32Array`, `Float64Array`, etc.).
+
+use std::marker::PhantomData;
+
+use neon_runtime;
+use neon_runtime::raw;
+
+use borrow::{Binary, Borrow, BorrowMut, LoanError, Ref, RefMut, TypedArrayTarget};
+use context::internal::Env;
+use context::Lock;
+use types::internal::ValueInternal;
+use types::{Handle, Managed, Object, Value};
+
+/// A JS typed array (e.g. `Int32Array`, `Float64Array`) whose elements are the Rust type `T`.
+#[repr(C)]
+pub struct JsTypedArray<T>(raw::Local, PhantomData<T>);
+
+impl<T> Clone for JsTypedArray<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for JsTypedArray<T> {}
+
+impl<T: Binary> Managed for JsTypedArray<T> {
+    fn to_raw(self) -> raw::Local {
+        self.0
+    }
+
+    fn from_raw(h: raw::Local) -> Self {
+        JsTypedArray(h, PhantomData)
+    }
+}
+
+impl<T: Binary> ValueInternal for JsTypedArray<T> {
+    fn name() -> String {
+        "TypedArray".to_string()
+    }
+
+    fn is_typeof<Other: Value>(env: Env, other: Other) -> bool {
+        unsafe {
+            neon_runtime::tag::is_typed_array(env.to_raw(), other.to_raw())
+                && neon_runtime::typedarray::info(env.to_raw(), other.to_raw()).ty == T::ARRAY_TYPE
+        }
+    }
+}
+
+impl<T: Binary> Value for JsTypedArray<T> {}
+
+impl<T: Binary> Object for JsTypedArray<T> {}
+
+impl<T: Binary> JsTypedArray<T> {
+    /// Queries the N-API runtime for this typed array's backing storage and packages it as a
+    /// `TypedArrayTarget`, keyed for the loan ledger on the address of the backing `ArrayBuffer`
+    /// rather than this view's own (possibly offset) data pointer.
+    unsafe fn target(self, lock: &Lock<'_>) -> TypedArrayTarget {
+        let info = neon_runtime::typedarray::info(lock.env.to_raw(), self.0);
+        let buffer = (info.data as *mut u8).sub(info.offset) as *mut _;
+        TypedArrayTarget::new(buffer, info.offset, info.ty, info.len)
+    }
+}
+
+impl<'a, T: Binary> Borrow for Handle<'a, JsTypedArray<T>> {
+    type Target = TypedArrayTarget;
+
+    fn try_borrow<'b>(self, lock: &'b Lock<'b>) -> Result<Ref<'b, TypedArrayTarget>, LoanError> {
+        let target = unsafe { self.target(lock) };
+        unsafe { Ref::new(lock, target) }
+    }
+}
+
+impl<'a, T: Binary> BorrowMut for Handle<'a, JsTypedArray<T>> {
+    fn try_borrow_mut<'b>(self, lock: &'b Lock<'b>) -> Result<RefMut<'b, TypedArrayTarget>, LoanError> {
+        let target = unsafe { self.target(lock) };
+        unsafe { RefMut::new(lock, target) }
+    }
+}